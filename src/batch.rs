@@ -0,0 +1,257 @@
+use ethers::contract::MULTICALL_ADDRESS;
+use ethers::providers::{Provider, Ws};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, Eip1559TransactionRequest, H256, U256};
+use eyre::Result;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::{
+    enforce_fee_caps, estimate_minimum_gas, get_minimum_gas_price, try_airdrop, AirdropClient,
+    AirdropState, EligiblePosition, GasPricing, IMulticall3, NonceTracker, IERC20, TransferFilter,
+};
+
+/// A recipient queued for the next batch dispatch.
+#[derive(Debug, Clone)]
+pub struct QueuedAirdrop {
+    pub recipient: Address,
+    pub amount: U256,
+    pub eligibility: Option<EligiblePosition>,
+}
+
+/// Recipients accumulated for the next batched transfer, flushed either
+/// when `max_size` is reached or by `run_batch_timer`'s window ticking.
+#[derive(Clone)]
+pub struct BatchQueue {
+    pending: Arc<Mutex<Vec<QueuedAirdrop>>>,
+    max_size: usize,
+}
+
+impl BatchQueue {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(Vec::new())),
+            max_size,
+        }
+    }
+
+    /// Queues `airdrop`, returning the accumulated batch if it just hit
+    /// `max_size` and should be dispatched immediately.
+    pub fn enqueue(&self, airdrop: QueuedAirdrop) -> Option<Vec<QueuedAirdrop>> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.push(airdrop);
+        if pending.len() >= self.max_size {
+            Some(pending.drain(..).collect())
+        } else {
+            None
+        }
+    }
+
+    fn drain(&self) -> Vec<QueuedAirdrop> {
+        self.pending.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// Flushes whatever has accumulated in `queue` every `window`, so recipients
+/// don't wait indefinitely for a batch to fill up.
+pub async fn run_batch_timer(
+    queue: BatchQueue,
+    window: Duration,
+    airdrop_token: IERC20<AirdropClient>,
+    provider: Provider<Ws>,
+    supports_eip1559: bool,
+    airdrop_state: Arc<Mutex<AirdropState>>,
+    pending_tx_count: Arc<AtomicUsize>,
+    nonce_tracker: Arc<NonceTracker>,
+) {
+    let mut interval = tokio::time::interval(window);
+    loop {
+        interval.tick().await;
+        let batch = queue.drain();
+        if !batch.is_empty() {
+            dispatch_batch(
+                batch,
+                &airdrop_token,
+                &provider,
+                supports_eip1559,
+                &airdrop_state,
+                &pending_tx_count,
+                &nonce_tracker,
+            )
+            .await;
+        }
+    }
+}
+
+/// Sends one multicall aggregating a `transfer` per recipient, then updates
+/// `AirdropState` from the receipt's `Transfer` logs so only addresses that
+/// actually received tokens get recorded. Falls back to sending each
+/// recipient individually (via the normal `try_airdrop` path, fee caps and
+/// all) if the batch call reverts.
+pub async fn dispatch_batch(
+    batch: Vec<QueuedAirdrop>,
+    airdrop_token: &IERC20<AirdropClient>,
+    provider: &Provider<Ws>,
+    supports_eip1559: bool,
+    airdrop_state: &Arc<Mutex<AirdropState>>,
+    pending_tx_count: &Arc<AtomicUsize>,
+    nonce_tracker: &Arc<NonceTracker>,
+) {
+    println!("📦 Dispatching batch of {} airdrop(s)", batch.len());
+
+    match send_batch_transfer(&batch, airdrop_token, provider, supports_eip1559, nonce_tracker).await {
+        Ok((tx_hash, transferred)) => {
+            println!("✅ Batch airdrop sent! Transaction: {:?}", tx_hash);
+
+            let mut state = airdrop_state.lock().unwrap();
+            for queued in &batch {
+                let recipient_str = format!("{:?}", queued.recipient);
+                if !transferred.contains(&queued.recipient) {
+                    // Not in the receipt's Transfer logs (e.g. this call
+                    // failed and was tolerated by its `allowFailure` flag) --
+                    // free it up for a retry instead of leaving it claimed
+                    // forever.
+                    state.release(&recipient_str);
+                    continue;
+                }
+                state.record_airdrop(
+                    recipient_str,
+                    queued.amount.to_string(),
+                    format!("{:?}", tx_hash),
+                    queued.eligibility,
+                );
+            }
+        }
+        Err(e) => {
+            println!(
+                "⚠️ Batch airdrop failed ({:?}), falling back to individual sends",
+                e
+            );
+            for queued in batch {
+                if let Err(e) = try_airdrop(
+                    queued.recipient,
+                    queued.amount,
+                    airdrop_token,
+                    provider,
+                    supports_eip1559,
+                    airdrop_state,
+                    pending_tx_count,
+                    // Already screened for duplicates/dust when queued.
+                    true,
+                    queued.eligibility,
+                    nonce_tracker,
+                )
+                .await
+                {
+                    println!(
+                        "❌ Individual fallback airdrop to {:?} failed: {:?}",
+                        queued.recipient, e
+                    );
+                    // try_airdrop skips its own release when the duplicate
+                    // check is bypassed, so this path -- the only one that
+                    // claimed the reservation -- has to free it itself.
+                    airdrop_state
+                        .lock()
+                        .unwrap()
+                        .release(&format!("{:?}", queued.recipient));
+                }
+            }
+        }
+    }
+}
+
+/// Aggregates one `transfer` call per recipient into a single Multicall3
+/// `aggregate3` transaction and, once mined, decodes the `Transfer` events
+/// out of the receipt's logs to determine which recipients actually got
+/// paid. Enforces the same fee caps as a single `try_airdrop` send, just
+/// against the summed gas of every leg in the batch, before it ever reaches
+/// the network. Assigns its nonce from `nonce_tracker` and resyncs it on
+/// failure exactly like `send_airdrop`, so a batch can't race an individual
+/// send (or another batch) for the same nonce.
+async fn send_batch_transfer(
+    batch: &[QueuedAirdrop],
+    airdrop_token: &IERC20<AirdropClient>,
+    provider: &Provider<Ws>,
+    supports_eip1559: bool,
+    nonce_tracker: &Arc<NonceTracker>,
+) -> Result<(H256, Vec<Address>)> {
+    let gas_pricing = get_minimum_gas_price(provider, supports_eip1559).await?;
+
+    let mut total_gas_limit = U256::zero();
+    for queued in batch {
+        total_gas_limit += estimate_minimum_gas(airdrop_token, queued.recipient, queued.amount).await?;
+    }
+    enforce_fee_caps(
+        gas_pricing,
+        total_gas_limit,
+        &format!("batch of {} recipient(s)", batch.len()),
+    )?;
+
+    let multicall3 = IMulticall3::new(MULTICALL_ADDRESS, airdrop_token.client());
+    let calls: Vec<(Address, bool, ethers::types::Bytes)> = batch
+        .iter()
+        .map(|queued| {
+            let calldata = airdrop_token
+                .transfer(queued.recipient, queued.amount)
+                .calldata()
+                .expect("transfer() call always encodes calldata");
+            (airdrop_token.address(), true, calldata)
+        })
+        .collect();
+    let tx_call = multicall3.aggregate_3(calls).gas(total_gas_limit);
+
+    let nonce = nonce_tracker.next(provider).await?;
+
+    let send_result = match gas_pricing {
+        GasPricing::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            let eip1559_tx = Eip1559TransactionRequest::new()
+                .to(MULTICALL_ADDRESS)
+                .data(tx_call.tx.data().cloned().unwrap_or_default())
+                .gas(total_gas_limit)
+                .nonce(nonce)
+                .max_fee_per_gas(max_fee_per_gas)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas);
+            let tx = tx_call.tx(TypedTransaction::Eip1559(eip1559_tx));
+            tx.send().await
+        }
+        GasPricing::Legacy { gas_price } => {
+            let tx = tx_call.gas(total_gas_limit).gas_price(gas_price).nonce(nonce).legacy();
+            tx.send().await
+        }
+    };
+
+    let pending_tx = match send_result {
+        Ok(pending_tx) => pending_tx,
+        Err(e) => {
+            // Same reasoning as `try_airdrop_inner`: the cached nonce may now
+            // be wrong in either direction, so drop it rather than reuse (or
+            // permanently skip) a stale value on the next batch/send.
+            nonce_tracker.resync().await;
+            return Err(e.into());
+        }
+    };
+    let tx_hash = pending_tx.tx_hash();
+    let receipt = pending_tx
+        .await?
+        .ok_or_else(|| eyre::eyre!("batch transaction {:?} dropped from the mempool", tx_hash))?;
+    if receipt.status.map_or(false, |status| status.is_zero()) {
+        eyre::bail!("batch transaction {:?} reverted", tx_hash);
+    }
+
+    let transferred = receipt
+        .logs
+        .into_iter()
+        .filter_map(|log| {
+            airdrop_token
+                .decode_event::<TransferFilter>("Transfer", log.topics, log.data)
+                .ok()
+        })
+        .map(|transfer| transfer.to)
+        .collect();
+
+    Ok((tx_hash, transferred))
+}
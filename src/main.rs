@@ -12,10 +12,82 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::env;
 use chrono::{DateTime, Utc};
 
+mod batch;
+mod server;
+
+/// Signing client used throughout the bot.
+///
+/// Deliberately *not* wrapped in a `GasEscalatorMiddleware`. Its
+/// `send_transaction` only knows how to rebroadcast `Legacy`/`Eip2930`
+/// transactions, so wrapping it unconditionally silently broke every
+/// EIP-1559 send (e.g. on Arbitrum, where `detect_eip1559_support` resolves
+/// `true` and this bot always runs -- see `main`'s `with_chain_id(42161u64)`).
+/// `get_minimum_gas_price` already prices `max_fee_per_gas` with 2x base-fee
+/// headroom, covering "don't get stuck when the base fee moves" for that
+/// branch.
+///
+/// The legacy branch gets no equivalent rebroadcast-of-a-stuck-tx mitigation.
+/// Giving it one would mean a second client type (escalator-wrapped for
+/// legacy chains, plain for EIP-1559 ones), which fans out into every
+/// `IERC20<AirdropClient>`/`INonfungiblePositionManager<AirdropClient>` and
+/// the `ControlState` that holds them -- a lot of surface area for a branch
+/// this deployment (hardcoded to Arbitrum) never exercises. Descoped rather
+/// than adding that generic split; revisit if this bot is ever pointed at a
+/// pre-London chain.
+///
+/// Also deliberately not wrapped in `NonceManagerMiddleware`: its
+/// `initialize_nonce` only queries the chain the first time it's called, so
+/// there's no way to force it to resync after a failed send. `NonceTracker`
+/// below assigns nonces the same way (a locally cached counter, so bursts of
+/// airdrops don't round-trip to the provider for every send) but supports a
+/// real resync.
+type AirdropClient = SignerMiddleware<Provider<Ws>, LocalWallet>;
+
+/// Assigns the nonce for each outgoing transaction from a locally cached
+/// counter, falling back to `eth_getTransactionCount` only when the cache is
+/// empty (startup, or after `resync`). Exists instead of `ethers`'
+/// `NonceManagerMiddleware` so a failed send can actually force a refetch:
+/// see the note on `AirdropClient`.
+struct NonceTracker {
+    address: Address,
+    cached: tokio::sync::Mutex<Option<U256>>,
+}
+
+impl NonceTracker {
+    fn new(address: Address) -> Self {
+        Self {
+            address,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Returns the next nonce to use. Holds the lock across the possible
+    /// `eth_getTransactionCount` round-trip so two concurrent callers can
+    /// never be handed the same value.
+    async fn next(&self, provider: &Provider<Ws>) -> Result<U256> {
+        let mut cached = self.cached.lock().await;
+        let nonce = match *cached {
+            Some(nonce) => nonce,
+            None => provider.get_transaction_count(self.address, None).await?,
+        };
+        *cached = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Drops the cached nonce so the next call to `next` re-fetches it from
+    /// the chain instead of reusing a value that may now be wrong -- e.g.
+    /// after a send errors out but the transaction actually landed anyway.
+    async fn resync(&self) {
+        *self.cached.lock().await = None;
+    }
+}
+
 // Generate type-safe bindings for the Uniswap NFT Position Manager contract
 abigen!(
     INonfungiblePositionManager,
@@ -32,20 +104,43 @@ abigen!(
     r#"[
         function transfer(address recipient, uint256 amount) external returns (bool)
         function balanceOf(address account) external view returns (uint256)
+        event Transfer(address indexed from, address indexed to, uint256 value)
     ]"#
 );
 
-#[derive(Debug, Serialize, Deserialize)]
+// Generate type-safe bindings for the Multicall3 contract, used by `batch`
+// to aggregate transfers into one transaction. Bound directly instead of
+// going through `ethers::contract::Multicall`: that wrapper's `send()`
+// builds and broadcasts its `TypedTransaction` internally with no way to
+// set the nonce, which every other send path gets from `NonceTracker`.
+abigen!(
+    IMulticall3,
+    r#"[
+        function aggregate3((address,bool,bytes)[] calls) external payable returns ((bool,bytes)[])
+    ]"#
+);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct AirdropRecord {
     address: String,
     timestamp: DateTime<Utc>,
     amount: String,
     tx_hash: String,
+    /// Pool (token0, token1, fee) the recipient qualified through, if this
+    /// airdrop was triggered by an eligible `IncreaseLiquidity` event.
+    qualifying_pool: Option<(Address, Address, u32)>,
+    /// The position's liquidity at the time it qualified.
+    qualifying_liquidity: Option<u128>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct AirdropState {
     recipients: HashMap<String, AirdropRecord>,
+    /// Addresses claimed by an in-flight send that hasn't recorded a result
+    /// yet. Not persisted: a crash mid-send should leave the address free to
+    /// retry on the next run, same as before this field existed.
+    #[serde(skip)]
+    pending: std::collections::HashSet<String>,
 }
 
 impl AirdropState {
@@ -69,13 +164,42 @@ impl AirdropState {
         self.recipients.contains_key(address)
     }
 
-    fn record_airdrop(&mut self, address: String, amount: String, tx_hash: String) {
+    /// Atomically checks and claims `address` so two concurrently spawned
+    /// sends to the same recipient can't both pass the duplicate check
+    /// before either has recorded a result. Returns `false` if `address`
+    /// already has a recorded airdrop or is already claimed by another
+    /// in-flight send; the caller must call `release` if it doesn't go on to
+    /// call `record_airdrop`.
+    fn try_reserve(&mut self, address: &str) -> bool {
+        if self.recipients.contains_key(address) || self.pending.contains(address) {
+            return false;
+        }
+        self.pending.insert(address.to_string());
+        true
+    }
+
+    /// Frees a reservation made by `try_reserve` without recording an
+    /// airdrop, so a failed send can be retried later.
+    fn release(&mut self, address: &str) {
+        self.pending.remove(address);
+    }
+
+    fn record_airdrop(
+        &mut self,
+        address: String,
+        amount: String,
+        tx_hash: String,
+        eligibility: Option<EligiblePosition>,
+    ) {
         let record = AirdropRecord {
             address: address.clone(),
             timestamp: Utc::now(),
             amount,
             tx_hash,
+            qualifying_pool: eligibility.map(|e| (e.token0, e.token1, e.fee)),
+            qualifying_liquidity: eligibility.map(|e| e.liquidity),
         };
+        self.pending.remove(&address);
         self.recipients.insert(address, record);
         if let Err(e) = self.save() {
             println!("⚠️ Failed to save airdrop state: {:?}", e);
@@ -83,23 +207,257 @@ impl AirdropState {
     }
 }
 
-async fn get_minimum_gas_price(provider: &Provider<Ws>) -> Result<U256> {
+/// Gas pricing strategy for a single transaction, resolved once per send
+/// based on whether the chain exposes `base_fee_per_gas` (EIP-1559).
+#[derive(Debug, Clone, Copy)]
+enum GasPricing {
+    Eip1559 {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+    Legacy {
+        gas_price: U256,
+    },
+}
+
+/// Detects whether the connected chain supports EIP-1559 by checking the
+/// latest block for a `base_fee_per_gas` field. Called once in `main()` so
+/// the same binary can target both legacy chains and post-London chains
+/// without per-send guesswork.
+async fn detect_eip1559_support(provider: &Provider<Ws>) -> Result<bool> {
+    let block = provider.get_block(BlockNumber::Latest).await?.unwrap();
+    Ok(block.base_fee_per_gas.is_some())
+}
+
+/// Returns the configured priority fee (miner tip) in wei, defaulting to
+/// ~0.01 gwei to match the bot's minimum-gas strategy.
+fn get_priority_fee() -> U256 {
+    let tip_gwei: f64 = env::var("PRIORITY_FEE_GWEI")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0.01);
+    U256::from((tip_gwei * 1_000_000_000.0) as u64)
+}
+
+async fn get_minimum_gas_price(
+    provider: &Provider<Ws>,
+    supports_eip1559: bool,
+) -> Result<GasPricing> {
     // Get the current base fee
     let block = provider.get_block(BlockNumber::Latest).await?.unwrap();
+
+    if let (true, Some(base_fee)) = (supports_eip1559, block.base_fee_per_gas) {
+        let max_priority_fee_per_gas = get_priority_fee();
+        // Survive base-fee swings between submission and inclusion.
+        let max_fee_per_gas = base_fee * 2 + max_priority_fee_per_gas;
+
+        println!("📊 Current base fee: {} gwei", base_fee / U256::exp10(9));
+        println!(
+            "📊 Using max fee: {} gwei (priority {} gwei)",
+            max_fee_per_gas / U256::exp10(9),
+            max_priority_fee_per_gas / U256::exp10(9)
+        );
+
+        return Ok(GasPricing::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        });
+    }
+
+    // Fall back to legacy pricing when the chain has no base fee.
     let base_fee = block.base_fee_per_gas.unwrap_or_default();
-    
+
     // Add 1% to base fee to ensure it passes
     // This is still extremely low but will work
     let gas_price = base_fee + (base_fee / 100);
-    
+
     println!("📊 Current base fee: {} gwei", base_fee / U256::exp10(9));
     println!("📊 Using gas price: {} gwei", gas_price / U256::exp10(9));
-    
-    Ok(gas_price)
+
+    Ok(GasPricing::Legacy { gas_price })
+}
+
+/// Absolute ceiling on the worst-case fee of a single airdrop, in wei.
+/// Defaults to 0.01 ETH so a base-fee spike can't quietly drain the
+/// funding wallet while the bot deliberately runs on minimum gas.
+fn max_absolute_fee() -> U256 {
+    env::var("MAX_ABSOLUTE_FEE_WEI")
+        .ok()
+        .and_then(|v| v.parse::<u128>().ok())
+        .map(U256::from)
+        .unwrap_or_else(|| U256::exp10(16))
+}
+
+/// ETH value of a single airdrop, used to derive the relative fee cap.
+/// There's no on-chain price oracle for the airdropped token, so operators
+/// set this manually; the relative cap is skipped (returns `None` from
+/// `max_relative_fee`) when left unset.
+fn airdrop_value_wei() -> U256 {
+    env::var("AIRDROP_VALUE_WEI")
+        .ok()
+        .and_then(|v| v.parse::<u128>().ok())
+        .map(U256::from)
+        .unwrap_or_default()
+}
+
+/// Relative ceiling on the worst-case fee, expressed as `MAX_FEE_RELATIVE_PERCENT`
+/// (default 3%) of `airdrop_value_wei`. Returns `None` when no airdrop value
+/// is configured, in which case only the absolute cap applies.
+fn max_relative_fee(airdrop_value_wei: U256) -> Option<U256> {
+    if airdrop_value_wei.is_zero() {
+        return None;
+    }
+    let percent: f64 = env::var("MAX_FEE_RELATIVE_PERCENT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3.0);
+    // Work in basis points to avoid floating point on the U256 side.
+    let basis_points = U256::from((percent * 100.0) as u64);
+    Some(airdrop_value_wei * basis_points / U256::from(10_000u64))
+}
+
+/// Aborts the airdrop if its worst-case fee (`gas_limit * max fee per gas`)
+/// exceeds either the absolute or relative cap, logging clearly which one
+/// was hit.
+fn enforce_fee_caps(gas_pricing: GasPricing, gas_limit: U256, recipient_str: &str) -> Result<()> {
+    let worst_case_fee = match gas_pricing {
+        GasPricing::Eip1559 { max_fee_per_gas, .. } => gas_limit * max_fee_per_gas,
+        GasPricing::Legacy { gas_price } => gas_limit * gas_price,
+    };
+
+    let absolute_cap = max_absolute_fee();
+    if worst_case_fee > absolute_cap {
+        println!(
+            "🛑 Worst-case fee {} wei exceeds absolute cap {} wei, aborting airdrop to {}",
+            worst_case_fee, absolute_cap, recipient_str
+        );
+        eyre::bail!(
+            "worst-case fee {} wei exceeds absolute cap {} wei",
+            worst_case_fee,
+            absolute_cap
+        );
+    }
+
+    if let Some(relative_cap) = max_relative_fee(airdrop_value_wei()) {
+        if worst_case_fee > relative_cap {
+            println!(
+                "🛑 Worst-case fee {} wei exceeds relative cap {} wei (MAX_FEE_RELATIVE_PERCENT of AIRDROP_VALUE_WEI), aborting airdrop to {}",
+                worst_case_fee, relative_cap, recipient_str
+            );
+            eyre::bail!(
+                "worst-case fee {} wei exceeds relative cap {} wei",
+                worst_case_fee,
+                relative_cap
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// A position that met the eligibility criteria, carried through to
+/// `AirdropRecord` so the state file documents why the recipient qualified.
+#[derive(Debug, Clone, Copy)]
+struct EligiblePosition {
+    token0: Address,
+    token1: Address,
+    fee: u32,
+    liquidity: u128,
+}
+
+/// Eligibility rules for which positions trigger an airdrop. Loaded once
+/// from the environment so an operator can tune thresholds without a
+/// redeploy.
+struct EligibilityCriteria {
+    min_liquidity: u128,
+    /// Empty means "any pool is allowed".
+    allowed_pools: Vec<(Address, Address, u32)>,
+    min_tick_range: Option<i32>,
+}
+
+/// Loads `EligibilityCriteria` from the environment:
+/// - `MIN_LIQUIDITY` (default 0)
+/// - `ALLOWED_POOLS`: comma-separated `token0-token1-fee` triples, e.g.
+///   `0xAAA..-0xBBB..-500,0xCCC..-0xDDD..-3000`. Empty/unset allows any pool.
+/// - `MIN_TICK_RANGE` (optional): minimum `tickUpper - tickLower` width.
+fn load_eligibility_criteria() -> Result<EligibilityCriteria> {
+    let min_liquidity = env::var("MIN_LIQUIDITY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0u128);
+
+    let allowed_pools = match env::var("ALLOWED_POOLS") {
+        Ok(raw) if !raw.trim().is_empty() => raw
+            .split(',')
+            .map(|pool| {
+                let parts: Vec<&str> = pool.trim().split('-').collect();
+                if parts.len() != 3 {
+                    eyre::bail!("ALLOWED_POOLS entry '{}' must be token0-token1-fee", pool);
+                }
+                Ok((parts[0].parse::<Address>()?, parts[1].parse::<Address>()?, parts[2].parse::<u32>()?))
+            })
+            .collect::<Result<Vec<_>>>()?,
+        _ => Vec::new(),
+    };
+
+    let min_tick_range = env::var("MIN_TICK_RANGE").ok().and_then(|v| v.parse().ok());
+
+    Ok(EligibilityCriteria {
+        min_liquidity,
+        allowed_pools,
+        min_tick_range,
+    })
+}
+
+/// Fetches the position's details via `positions()` and checks them against
+/// `criteria`, returning the qualifying pool/liquidity on success.
+async fn check_eligibility(
+    nft_manager: &INonfungiblePositionManager<AirdropClient>,
+    token_id: U256,
+    criteria: &EligibilityCriteria,
+) -> Result<Option<EligiblePosition>> {
+    let (_nonce, _operator, token0, token1, fee, tick_lower, tick_upper, liquidity, ..) =
+        nft_manager.positions(token_id).call().await?;
+
+    if liquidity < criteria.min_liquidity {
+        println!(
+            "⏭️ Position {} liquidity {} below minimum {}, skipping",
+            token_id, liquidity, criteria.min_liquidity
+        );
+        return Ok(None);
+    }
+
+    if !criteria.allowed_pools.is_empty()
+        && !criteria.allowed_pools.contains(&(token0, token1, fee))
+    {
+        println!(
+            "⏭️ Position {} pool ({:?}, {:?}, {}) is not on the allowlist, skipping",
+            token_id, token0, token1, fee
+        );
+        return Ok(None);
+    }
+
+    if let Some(min_tick_range) = criteria.min_tick_range {
+        let tick_range = tick_upper - tick_lower;
+        if tick_range < min_tick_range {
+            println!(
+                "⏭️ Position {} tick range {} below minimum {}, skipping",
+                token_id, tick_range, min_tick_range
+            );
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(EligiblePosition {
+        token0,
+        token1,
+        fee,
+        liquidity,
+    }))
 }
 
 async fn estimate_minimum_gas(
-    token: &IERC20<SignerMiddleware<Provider<Ws>, LocalWallet>>,
+    token: &IERC20<AirdropClient>,
     recipient: Address,
     amount: U256,
 ) -> Result<U256> {
@@ -113,32 +471,296 @@ async fn estimate_minimum_gas(
     Ok(gas_estimate + (gas_estimate / 50))
 }
 
+/// Handles a single `IncreaseLiquidity` event end-to-end: looks up the
+/// position owner, skips already-airdropped addresses, then estimates gas
+/// and sends the transfer. Spawned as its own task per event so a burst of
+/// new positions airdrops concurrently instead of serializing on a single
+/// slow `estimate_gas` call.
+async fn process_liquidity_event(
+    token_id: U256,
+    liquidity: u128,
+    nft_manager: INonfungiblePositionManager<AirdropClient>,
+    airdrop_token: IERC20<AirdropClient>,
+    provider: Provider<Ws>,
+    supports_eip1559: bool,
+    airdrop_state: Arc<Mutex<AirdropState>>,
+    pending_tx_count: Arc<AtomicUsize>,
+    eligibility_criteria: Arc<EligibilityCriteria>,
+    batch_queue: Option<batch::BatchQueue>,
+    nonce_tracker: Arc<NonceTracker>,
+) {
+    println!("🔥 New liquidity added!");
+    println!("Token ID: {}", token_id);
+    println!("Liquidity Amount: {}", liquidity);
+
+    let owner = match nft_manager.owner_of(token_id).call().await {
+        Ok(owner) => owner,
+        Err(e) => {
+            println!("❌ Failed to get position owner: {:?}", e);
+            return;
+        }
+    };
+    println!("Position Owner: {:?}", owner);
+
+    let eligibility = match check_eligibility(&nft_manager, token_id, &eligibility_criteria).await {
+        Ok(Some(eligibility)) => eligibility,
+        Ok(None) => return,
+        Err(e) => {
+            println!("❌ Failed to check position eligibility: {:?}", e);
+            return;
+        }
+    };
+
+    // Send airdrop (100 tokens with 18 decimals)
+    let amount = U256::from(100_000_000_000_000_000_000u128);
+
+    if let Some(batch_queue) = batch_queue {
+        // Screen out obvious no-sends before queuing; a reverted batch
+        // falls back to try_airdrop, which re-checks dust and fee caps but
+        // skips its duplicate check, relying on the reservation made here.
+        if amount.is_zero() {
+            println!("⏭️ Airdrop amount rounds to zero (dust), skipping {:?}", owner);
+            return;
+        }
+        let owner_str = format!("{:?}", owner);
+        if !airdrop_state.lock().unwrap().try_reserve(&owner_str) {
+            println!("⏭️ Address {:?} has already received an airdrop, skipping...", owner);
+            return;
+        }
+
+        let queued = batch::QueuedAirdrop {
+            recipient: owner,
+            amount,
+            eligibility: Some(eligibility),
+        };
+        if let Some(ready) = batch_queue.enqueue(queued) {
+            batch::dispatch_batch(
+                ready,
+                &airdrop_token,
+                &provider,
+                supports_eip1559,
+                &airdrop_state,
+                &pending_tx_count,
+                &nonce_tracker,
+            )
+            .await;
+        }
+        return;
+    }
+
+    if let Err(e) = try_airdrop(
+        owner,
+        amount,
+        &airdrop_token,
+        &provider,
+        supports_eip1559,
+        &airdrop_state,
+        &pending_tx_count,
+        false,
+        Some(eligibility),
+        &nonce_tracker,
+    )
+    .await
+    {
+        println!("❌ Failed to airdrop to {:?}: {:?}", owner, e);
+    }
+}
+
+/// Attempts to send an airdrop to `recipient`, skipping it when
+/// `skip_duplicate_check` is false and the address has already received
+/// one. Shared by the liquidity-event handler and the control server's
+/// manual-airdrop endpoint so both paths go through the same gas pricing,
+/// sending, recording, and nonce-resync logic.
+async fn try_airdrop(
+    recipient: Address,
+    amount: U256,
+    airdrop_token: &IERC20<AirdropClient>,
+    provider: &Provider<Ws>,
+    supports_eip1559: bool,
+    airdrop_state: &Arc<Mutex<AirdropState>>,
+    pending_tx_count: &Arc<AtomicUsize>,
+    skip_duplicate_check: bool,
+    eligibility: Option<EligiblePosition>,
+    nonce_tracker: &Arc<NonceTracker>,
+) -> Result<H256> {
+    let recipient_str = format!("{:?}", recipient);
+
+    // Reserve the recipient under the same lock as the duplicate check so
+    // two concurrently spawned sends to the same address can't both pass it
+    // before either has recorded a result.
+    if !skip_duplicate_check && !airdrop_state.lock().unwrap().try_reserve(&recipient_str) {
+        println!("⏭️ Address {} has already received an airdrop, skipping...", recipient_str);
+        eyre::bail!("address {} has already received an airdrop", recipient_str);
+    }
+
+    // From here on, any early return must release the reservation above (if
+    // we made one) so a transient failure doesn't permanently block retries.
+    let result = try_airdrop_inner(
+        recipient,
+        recipient_str.clone(),
+        amount,
+        airdrop_token,
+        provider,
+        supports_eip1559,
+        pending_tx_count,
+        nonce_tracker,
+    )
+    .await;
+
+    match &result {
+        Ok(tx_hash) => {
+            println!("✅ Airdrop sent to {}! Transaction: {:?}", recipient_str, tx_hash);
+
+            airdrop_state.lock().unwrap().record_airdrop(
+                recipient_str,
+                amount.to_string(),
+                format!("{:?}", tx_hash),
+                eligibility,
+            );
+        }
+        Err(e) => {
+            println!("❌ Failed to send airdrop: {:?}", e);
+            println!("💡 Make sure you have enough ETH in your wallet for gas fees!");
+
+            if !skip_duplicate_check {
+                airdrop_state.lock().unwrap().release(&recipient_str);
+            }
+        }
+    }
+
+    result
+}
+
+/// The fallible part of `try_airdrop` after the duplicate check: pricing,
+/// gas estimation, fee-cap enforcement, and the send itself. Split out so
+/// `try_airdrop` can release its reservation on any error path via a single
+/// `match` instead of repeating the release call at every `?`/`bail!`.
+async fn try_airdrop_inner(
+    recipient: Address,
+    recipient_str: String,
+    amount: U256,
+    airdrop_token: &IERC20<AirdropClient>,
+    provider: &Provider<Ws>,
+    supports_eip1559: bool,
+    pending_tx_count: &Arc<AtomicUsize>,
+    nonce_tracker: &Arc<NonceTracker>,
+) -> Result<H256> {
+    if amount.is_zero() {
+        println!("⏭️ Airdrop amount rounds to zero (dust), skipping send to {}", recipient_str);
+        eyre::bail!("airdrop amount is dust (zero) for {}", recipient_str);
+    }
+
+    let gas_pricing = get_minimum_gas_price(provider, supports_eip1559).await?;
+    let gas_limit = estimate_minimum_gas(airdrop_token, recipient, amount).await?;
+
+    println!("💡 Estimated gas limit: {}", gas_limit);
+
+    enforce_fee_caps(gas_pricing, gas_limit, &recipient_str)?;
+
+    let nonce = nonce_tracker.next(provider).await?;
+
+    // Claimed here and released below on any exit path: `send_airdrop` only
+    // releases it (via the confirmation-tracking task it spawns) once the
+    // send actually succeeds, so a failed send has to release it itself.
+    pending_tx_count.fetch_add(1, Ordering::Relaxed);
+    let result = send_airdrop(
+        airdrop_token,
+        recipient,
+        amount,
+        gas_pricing,
+        gas_limit,
+        nonce,
+        provider.clone(),
+        pending_tx_count.clone(),
+    )
+    .await;
+
+    if result.is_ok() {
+        println!("⚡ Used gas limit: {}", gas_limit);
+    } else {
+        pending_tx_count.fetch_sub(1, Ordering::Relaxed);
+
+        // The cached nonce may now be wrong in either direction -- e.g. the
+        // send could have actually broadcast before the RPC call errored, or
+        // failed before broadcast and left our counter ahead of the chain's.
+        // Drop it so the next send re-fetches from the node instead of
+        // reusing (or permanently skipping) a stale value.
+        nonce_tracker.resync().await;
+    }
+
+    result
+}
+
+/// Sends the transfer and returns as soon as it's broadcast. `pending_tx_count`
+/// stays incremented until the transaction is actually mined (or dropped, or
+/// the receipt lookup errors) -- tracked by a spawned task polling for the
+/// receipt -- so `/status` reflects
+/// genuinely unconfirmed/stuck transactions rather than just the instant
+/// between broadcast and the RPC call returning.
 async fn send_airdrop(
-    token: &IERC20<SignerMiddleware<Provider<Ws>, LocalWallet>>,
+    token: &IERC20<AirdropClient>,
     recipient: Address,
     amount: U256,
-    gas_price: U256,
+    gas_pricing: GasPricing,
     gas_limit: U256,
+    nonce: U256,
+    provider: Provider<Ws>,
+    pending_tx_count: Arc<AtomicUsize>,
 ) -> Result<H256> {
     let tx_call = token.transfer(recipient, amount);
-    
-    // Use legacy transaction type which often uses less gas
-    let tx = tx_call
-        .gas(gas_limit)
-        .gas_price(gas_price)
-        .legacy();
 
-    let pending_tx = tx.send().await?;
-    Ok(pending_tx.tx_hash())
+    let pending_tx = match gas_pricing {
+        GasPricing::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        } => {
+            // abigen's ContractCall only exposes a flat gas_price setter, so
+            // build the typed 1559 request directly to set the tip as well.
+            let eip1559_tx = Eip1559TransactionRequest::new()
+                .to(token.address())
+                .data(tx_call.tx.data().cloned().unwrap_or_default())
+                .gas(gas_limit)
+                .nonce(nonce)
+                .max_fee_per_gas(max_fee_per_gas)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas);
+            let tx = tx_call.tx(TypedTransaction::Eip1559(eip1559_tx));
+            tx.send().await?
+        }
+        GasPricing::Legacy { gas_price } => {
+            // Use legacy transaction type which often uses less gas
+            let tx = tx_call.gas(gas_limit).gas_price(gas_price).nonce(nonce).legacy();
+            tx.send().await?
+        }
+    };
+
+    let tx_hash = pending_tx.tx_hash();
+    tokio::spawn(async move {
+        loop {
+            match provider.get_transaction_receipt(tx_hash).await {
+                Ok(Some(_)) | Err(_) => break,
+                Ok(None) => {
+                    // No receipt and no longer known to the node: dropped.
+                    if let Ok(None) = provider.get_transaction(tx_hash).await {
+                        break;
+                    }
+                    tokio::time::sleep(Duration::from_secs(5)).await
+                }
+            }
+        }
+        pending_tx_count.fetch_sub(1, Ordering::Relaxed);
+    });
+
+    Ok(tx_hash)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
 
-    // Load airdrop state
-    let mut airdrop_state = AirdropState::load();
+    // Load airdrop state. Shared across concurrently spawned airdrop tasks.
+    let airdrop_state = AirdropState::load();
     println!("📝 Loaded airdrop state with {} previous recipients", airdrop_state.recipients.len());
+    let airdrop_state = Arc::new(Mutex::new(airdrop_state));
 
     // Connect to Arbitrum network
     let ws_url = env::var("ALCHEMY_API_KEY").expect("ALCHEMY_API_KEY must be set");
@@ -147,9 +769,13 @@ async fn main() -> Result<()> {
     // Set up wallet
     let private_key = env::var("PRIVATE_KEY").expect("PRIVATE_KEY must be set");
     let wallet = private_key.parse::<LocalWallet>()?.with_chain_id(42161u64);
-    let client = SignerMiddleware::new(provider.clone(), wallet.clone());
+    let client: AirdropClient = SignerMiddleware::new(provider.clone(), wallet.clone());
     let client = Arc::new(client);
 
+    // Assigns nonces locally so bursts of liquidity events can be sent
+    // concurrently without round-tripping to the provider for every send.
+    let nonce_tracker = Arc::new(NonceTracker::new(wallet.address()));
+
     // Contract addresses
     let nft_manager_address: Address = env::var("UNISWAP_NFT_POSITION_MANAGER")
         .expect("UNISWAP_NFT_POSITION_MANAGER must be set")
@@ -164,62 +790,102 @@ async fn main() -> Result<()> {
 
     // Listen for IncreaseLiquidity events
     let event = nft_manager.event::<IncreaseLiquidityFilter>();
-    let mut stream = event.stream().await?;
+    let mut stream = event.stream_with_meta().await?;
+
+    // Detect once whether this chain speaks EIP-1559 so the same binary
+    // works unmodified on legacy chains and post-London chains alike.
+    let supports_eip1559 = detect_eip1559_support(&provider).await?;
+    println!(
+        "⛓️ Chain fee market: {}",
+        if supports_eip1559 { "EIP-1559" } else { "legacy" }
+    );
+
+    // Positions must meet these criteria to qualify for an airdrop.
+    let eligibility_criteria = Arc::new(load_eligibility_criteria()?);
+    println!(
+        "🔍 Eligibility: min liquidity {}, {} allowed pool(s), min tick range {:?}",
+        eligibility_criteria.min_liquidity,
+        eligibility_criteria.allowed_pools.len(),
+        eligibility_criteria.min_tick_range
+    );
+
+    // Liveness counters surfaced by the control server's /status endpoint.
+    let last_processed_block = Arc::new(AtomicU64::new(0));
+    let pending_tx_count = Arc::new(AtomicUsize::new(0));
+
+    // Optional batching: accumulate eligible recipients and dispatch them
+    // in one multicall instead of paying per-transfer gas overhead.
+    let batch_enabled = env::var("BATCH_MODE")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let batch_queue = if batch_enabled {
+        let max_size: usize = env::var("BATCH_MAX_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        let window_ms: u64 = env::var("BATCH_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10_000);
+        println!(
+            "📦 Batch mode enabled: up to {} recipient(s) or every {}ms",
+            max_size, window_ms
+        );
+
+        let queue = batch::BatchQueue::new(max_size);
+        tokio::spawn(batch::run_batch_timer(
+            queue.clone(),
+            std::time::Duration::from_millis(window_ms),
+            airdrop_token.clone(),
+            provider.clone(),
+            supports_eip1559,
+            airdrop_state.clone(),
+            pending_tx_count.clone(),
+            nonce_tracker.clone(),
+        ));
+        Some(queue)
+    } else {
+        None
+    };
+
+    // Run the control server alongside the event stream so operators can
+    // inspect state and trigger manual airdrops without killing the bot.
+    let control_state = server::ControlState {
+        airdrop_state: airdrop_state.clone(),
+        airdrop_token: airdrop_token.clone(),
+        provider: provider.clone(),
+        supports_eip1559,
+        last_processed_block: last_processed_block.clone(),
+        pending_tx_count: pending_tx_count.clone(),
+        nonce_tracker: nonce_tracker.clone(),
+    };
+    tokio::spawn(async move {
+        if let Err(e) = server::run(control_state).await {
+            println!("❌ Control server error: {:?}", e);
+        }
+    });
 
     println!("🎯 Monitoring for new liquidity provisions...");
     println!("⛽ Using absolute minimum gas (0.001 gwei) for Arbitrum");
 
-    while let Some(Ok(event)) = stream.next().await {
-        println!("🔥 New liquidity added!");
-        println!("Token ID: {}", event.token_id);
-        println!("Liquidity Amount: {}", event.liquidity);
-
-        // Get the owner of the NFT position
-        match nft_manager.owner_of(event.token_id).call().await {
-            Ok(owner) => {
-                let owner_str = format!("{:?}", owner);
-                println!("Position Owner: {}", owner_str);
-
-                // Check if this address has already received an airdrop
-                if airdrop_state.has_received_airdrop(&owner_str) {
-                    println!("⏭️ Address {} has already received an airdrop, skipping...", owner_str);
-                    continue;
-                }
-                
-                // Send airdrop (100 tokens with 18 decimals)
-                let amount = U256::from(100_000_000_000_000_000_000u128);
-
-                // Get the minimum viable gas price and limit
-                let gas_price = get_minimum_gas_price(&provider).await?;
-                let gas_limit = estimate_minimum_gas(&airdrop_token, owner, amount).await?;
-                
-                println!("💡 Using minimum gas price: {} gwei", gas_price / U256::exp10(9));
-                println!("💡 Estimated gas limit: {}", gas_limit);
-
-                // Send the airdrop with minimum viable gas
-                match send_airdrop(&airdrop_token, owner, amount, gas_price, gas_limit).await {
-                    Ok(tx_hash) => {
-                        println!("✅ Airdrop sent to {}! Transaction: {:?}", owner_str, tx_hash);
-                        println!("💰 Used gas price: {} gwei", gas_price / U256::exp10(9));
-                        println!("⚡ Used gas limit: {}", gas_limit);
-                        
-                        // Record the airdrop
-                        airdrop_state.record_airdrop(
-                            owner_str,
-                            amount.to_string(),
-                            format!("{:?}", tx_hash),
-                        );
-                    }
-                    Err(e) => {
-                        println!("❌ Failed to send airdrop: {:?}", e);
-                        println!("💡 Make sure you have enough ETH in your wallet for gas fees!");
-                    }
-                }
-            }
-            Err(e) => {
-                println!("❌ Failed to get position owner: {:?}", e);
-            }
-        }
+    while let Some(Ok((event, meta))) = stream.next().await {
+        last_processed_block.store(meta.block_number.as_u64(), Ordering::Relaxed);
+
+        // Spawn each event onto its own task so a burst of new positions
+        // airdrops concurrently instead of serializing on one slow call.
+        tokio::spawn(process_liquidity_event(
+            event.token_id,
+            event.liquidity,
+            nft_manager.clone(),
+            airdrop_token.clone(),
+            provider.clone(),
+            supports_eip1559,
+            airdrop_state.clone(),
+            pending_tx_count.clone(),
+            eligibility_criteria.clone(),
+            batch_queue.clone(),
+            nonce_tracker.clone(),
+        ));
     }
 
     Ok(())
@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use ethers::providers::{Provider, Ws};
+use ethers::types::{Address, U256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{try_airdrop, AirdropClient, AirdropRecord, AirdropState, NonceTracker, IERC20};
+
+/// Shared state for the control server: everything `try_airdrop` needs to
+/// send a transfer, plus the counters used for the liveness report.
+#[derive(Clone)]
+pub struct ControlState {
+    pub airdrop_state: Arc<Mutex<AirdropState>>,
+    pub airdrop_token: IERC20<AirdropClient>,
+    pub provider: Provider<Ws>,
+    pub supports_eip1559: bool,
+    pub last_processed_block: Arc<AtomicU64>,
+    pub pending_tx_count: Arc<AtomicUsize>,
+    pub nonce_tracker: Arc<NonceTracker>,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusResponse {
+    last_processed_block: u64,
+    pending_tx_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct HasReceivedResponse {
+    address: String,
+    has_received_airdrop: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManualAirdropRequest {
+    address: Address,
+    /// Amount in wei; defaults to the standard 100-token airdrop when omitted.
+    amount: Option<U256>,
+    /// Send even if the address already received an airdrop.
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ManualAirdropResponse {
+    tx_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+async fn list_recipients(
+    State(state): State<ControlState>,
+) -> Json<Vec<AirdropRecord>> {
+    let recipients = state
+        .airdrop_state
+        .lock()
+        .unwrap()
+        .recipients
+        .values()
+        .cloned()
+        .collect();
+    Json(recipients)
+}
+
+async fn has_received_airdrop(
+    State(state): State<ControlState>,
+    Path(address): Path<String>,
+) -> Result<Json<HasReceivedResponse>, Json<ErrorResponse>> {
+    // AirdropState keys are lowercase hex (format!("{:?}", Address)), so
+    // comparing the raw path string against them is case-sensitive -- parse
+    // and reformat the same way `ManualAirdropRequest.address` does instead
+    // of looking up whatever casing the caller happened to send.
+    let address: Address = address
+        .parse()
+        .map_err(|e| Json(ErrorResponse { error: format!("invalid address: {:?}", e) }))?;
+    let address = format!("{:?}", address);
+
+    let has_received_airdrop = state
+        .airdrop_state
+        .lock()
+        .unwrap()
+        .has_received_airdrop(&address);
+    Ok(Json(HasReceivedResponse {
+        address,
+        has_received_airdrop,
+    }))
+}
+
+async fn status(State(state): State<ControlState>) -> Json<StatusResponse> {
+    Json(StatusResponse {
+        last_processed_block: state.last_processed_block.load(Ordering::Relaxed),
+        pending_tx_count: state.pending_tx_count.load(Ordering::Relaxed),
+    })
+}
+
+async fn trigger_airdrop(
+    State(state): State<ControlState>,
+    Json(req): Json<ManualAirdropRequest>,
+) -> Result<Json<ManualAirdropResponse>, Json<ErrorResponse>> {
+    let amount = req
+        .amount
+        .unwrap_or_else(|| U256::from(100_000_000_000_000_000_000u128));
+
+    try_airdrop(
+        req.address,
+        amount,
+        &state.airdrop_token,
+        &state.provider,
+        state.supports_eip1559,
+        &state.airdrop_state,
+        &state.pending_tx_count,
+        req.force,
+        None,
+        &state.nonce_tracker,
+    )
+    .await
+    .map(|tx_hash| Json(ManualAirdropResponse { tx_hash: format!("{:?}", tx_hash) }))
+    .map_err(|e| Json(ErrorResponse { error: e.to_string() }))
+}
+
+fn router(state: ControlState) -> Router {
+    Router::new()
+        .route("/recipients", get(list_recipients))
+        .route("/recipients/:address/has_received", get(has_received_airdrop))
+        .route("/airdrop", post(trigger_airdrop))
+        .route("/status", get(status))
+        .with_state(state)
+}
+
+/// Runs the embedded control server alongside the event stream so operators
+/// can inspect state and trigger manual airdrops without killing the bot.
+/// Listens on `CONTROL_SERVER_ADDR` (default `127.0.0.1:3030`).
+pub async fn run(state: ControlState) -> Result<()> {
+    let addr = env::var("CONTROL_SERVER_ADDR").unwrap_or_else(|_| "127.0.0.1:3030".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    println!("🛰️ Control server listening on http://{}", addr);
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}